@@ -1,9 +1,13 @@
 use std::fmt;
 
-#[derive(Clone, Copy)]
+use crate::write::AnyWrite;
+
+#[derive(Clone)]
 #[repr(u8)]
 /// ANSI color codes. Includes 16 basic colors, 256 colors, RGB and HEX.
-/// Note: Expected format for HEX is `#RRGGBB`.
+/// Accepts `#RGB`, `#RRGGBB`, `#RGBA` and `#RRGGBBAA` hex strings via `Color::from_hex` or
+/// `Color::from_hex_on`, the only ways to produce a `Hex` color: its inner `HexColor` has a private field, so this variant
+/// can't be constructed with an arbitrary unvalidated string from outside the crate.
 pub enum Color {
     Reset = 0,
     Black = 30,
@@ -24,35 +28,213 @@ pub enum Color {
     BrightWhite,
     Rgb(u8, u8, u8),
     C256(u8),
-    Hex(&'static str),
+    Hex(HexColor),
+}
+
+/// An already-validated hex color string, e.g. `"#ff0000"`. The field is private so only
+/// `Color::from_hex`/`Color::from_hex_on` can produce one, closing off the typo-silently-renders-
+/// as-black footgun that a raw `Color::Hex(String)` would reopen.
+#[derive(Clone)]
+pub struct HexColor(String);
+
+impl HexColor {
+    fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// RGB values for the 16 basic ANSI colors, in the same order as the `Color` variants
+/// `Black..=BrightWhite`. Used to resolve a basic color to a concrete RGB triple, e.g. when
+/// blending it into a gradient.
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The 16 basic `Color` variants paired with their approximate RGB value, in the same order
+/// as `ANSI16_RGB`. Used to find the nearest basic color to an arbitrary RGB triple.
+const ANSI16_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, ANSI16_RGB[0]),
+    (Color::Red, ANSI16_RGB[1]),
+    (Color::Green, ANSI16_RGB[2]),
+    (Color::Yellow, ANSI16_RGB[3]),
+    (Color::Blue, ANSI16_RGB[4]),
+    (Color::Magenta, ANSI16_RGB[5]),
+    (Color::Cyan, ANSI16_RGB[6]),
+    (Color::White, ANSI16_RGB[7]),
+    (Color::BrightBlack, ANSI16_RGB[8]),
+    (Color::BrightRed, ANSI16_RGB[9]),
+    (Color::BrightGreen, ANSI16_RGB[10]),
+    (Color::BrightYellow, ANSI16_RGB[11]),
+    (Color::BrightBlue, ANSI16_RGB[12]),
+    (Color::BrightMagenta, ANSI16_RGB[13]),
+    (Color::BrightCyan, ANSI16_RGB[14]),
+    (Color::BrightWhite, ANSI16_RGB[15]),
+];
+
+/// The color capability of the target terminal, from most to least expressive. Used with
+/// `Lipbalm::color_level` to downgrade `Rgb`/`Hex`/`C256` colors to whatever the terminal can
+/// actually render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// 24-bit RGB, rendered as-is.
+    TrueColor,
+    /// Downgrade to the nearest of the 256 xterm palette colors.
+    Ansi256,
+    /// Downgrade to the nearest of the 16 basic ANSI colors.
+    Ansi16,
 }
 
 impl Color {
-    pub(crate) fn to_ansi(self) -> String {
+    /// Parse a hex color string, returning a descriptive error instead of silently rendering
+    /// black on a typo. Accepts `#RGB`, `#RRGGBB`, `#RGBA` and `#RRGGBBAA` (case-insensitive,
+    /// leading `#` optional). Any alpha channel is blended against black.
+    pub fn from_hex(input: &str) -> Result<Color, ParseColorError> {
+        Color::from_hex_on(input, (0, 0, 0))
+    }
+
+    /// Like `from_hex`, but blends any alpha channel against `background` instead of black.
+    pub fn from_hex_on(input: &str, background: (u8, u8, u8)) -> Result<Color, ParseColorError> {
+        let (r, g, b, a) = parse_hex_digits(input)?;
+        let blend = |fg: u8, bg: u8| -> u8 {
+            ((fg as u16 * a as u16 + bg as u16 * (255 - a as u16)) / 255) as u8
+        };
+        Ok(Color::Hex(HexColor(format!(
+            "#{:02x}{:02x}{:02x}",
+            blend(r, background.0),
+            blend(g, background.1),
+            blend(b, background.2)
+        ))))
+    }
+
+    /// Downgrade this color to fit the given capability `level`. Basic 16-color variants are
+    /// already supported everywhere and pass through unchanged.
+    pub(crate) fn downgrade(&self, level: Level) -> Color {
+        match level {
+            Level::TrueColor => self.clone(),
+            Level::Ansi256 => match self {
+                Color::Rgb(r, g, b) => Color::C256(nearest_ansi256(*r, *g, *b)),
+                Color::Hex(s) => {
+                    let (r, g, b) = hex_to_rgb(s.as_str());
+                    Color::C256(nearest_ansi256(r, g, b))
+                }
+                other => other.clone(),
+            },
+            Level::Ansi16 => match self {
+                Color::Rgb(r, g, b) => nearest_ansi16(*r, *g, *b),
+                Color::Hex(s) => {
+                    let (r, g, b) = hex_to_rgb(s.as_str());
+                    nearest_ansi16(r, g, b)
+                }
+                Color::C256(c) => {
+                    let (r, g, b) = c256_to_rgb(*c);
+                    nearest_ansi16(r, g, b)
+                }
+                other => other.clone(),
+            },
+        }
+    }
+
+    /// Resolve this color to a concrete `(r, g, b)` triple, approximating the 16 basic colors
+    /// and looking up the xterm 256-color palette where needed.
+    pub(crate) fn to_rgb(&self) -> (u8, u8, u8) {
+        match self {
+            // `Reset` has no fixed color on a real terminal; white is the closest thing to a
+            // "no color" stand-in so it can still be used as a gradient stop instead of panicking.
+            Color::Reset => (255, 255, 255),
+            Color::Black => ANSI16_RGB[0],
+            Color::Red => ANSI16_RGB[1],
+            Color::Green => ANSI16_RGB[2],
+            Color::Yellow => ANSI16_RGB[3],
+            Color::Blue => ANSI16_RGB[4],
+            Color::Magenta => ANSI16_RGB[5],
+            Color::Cyan => ANSI16_RGB[6],
+            Color::White => ANSI16_RGB[7],
+            Color::BrightBlack => ANSI16_RGB[8],
+            Color::BrightRed => ANSI16_RGB[9],
+            Color::BrightGreen => ANSI16_RGB[10],
+            Color::BrightYellow => ANSI16_RGB[11],
+            Color::BrightBlue => ANSI16_RGB[12],
+            Color::BrightMagenta => ANSI16_RGB[13],
+            Color::BrightCyan => ANSI16_RGB[14],
+            Color::BrightWhite => ANSI16_RGB[15],
+            Color::Rgb(r, g, b) => (*r, *g, *b),
+            Color::C256(c) => c256_to_rgb(*c),
+            Color::Hex(s) => hex_to_rgb(s.as_str()),
+        }
+    }
+
+    pub(crate) fn to_ansi(&self) -> String {
         match self {
-            Color::Reset => "0".to_string(),
-            Color::Black => "30".to_string(),
-            Color::Red => "31".to_string(),
-            Color::Green => "32".to_string(),
-            Color::Yellow => "33".to_string(),
-            Color::Blue => "34".to_string(),
-            Color::Magenta => "35".to_string(),
-            Color::Cyan => "36".to_string(),
-            Color::White => "37".to_string(),
-            Color::BrightBlack => "90".to_string(),
-            Color::BrightRed => "91".to_string(),
-            Color::BrightGreen => "92".to_string(),
-            Color::BrightYellow => "93".to_string(),
-            Color::BrightBlue => "94".to_string(),
-            Color::BrightMagenta => "95".to_string(),
-            Color::BrightCyan => "96".to_string(),
-            Color::BrightWhite => "97".to_string(),
             Color::C256(c) => format!("5;{}", c),
             Color::Rgb(r, g, b) => format!("2;{};{};{}", r, g, b),
             Color::Hex(s) => {
-                let (r, g, b) = hex_to_rgb(s.to_string());
+                let (r, g, b) = hex_to_rgb(s.as_str());
                 format!("2;{};{};{}", r, g, b)
             }
+            _ => self.basic_code().to_string(),
+        }
+    }
+
+    /// The bare numeric SGR code for the 16 basic colors (and `Reset`). Only meaningful for
+    /// those unit variants; `Rgb`/`C256`/`Hex` compute their own code dynamically instead.
+    fn basic_code(&self) -> u8 {
+        match self {
+            Color::Reset => 0,
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::BrightBlack => 90,
+            Color::BrightRed => 91,
+            Color::BrightGreen => 92,
+            Color::BrightYellow => 93,
+            Color::BrightBlue => 94,
+            Color::BrightMagenta => 95,
+            Color::BrightCyan => 96,
+            Color::BrightWhite => 97,
+            Color::Rgb(_, _, _) | Color::C256(_) | Color::Hex(_) => {
+                unreachable!("basic_code is only called for the 16 basic color variants and Reset")
+            }
+        }
+    }
+
+    /// Writes this color's SGR code to `w` directly, without ever allocating a `String` for
+    /// the 16 basic colors (or `Reset`) — mirrors `to_ansi`, but streams to a sink instead of
+    /// building one. `prefix` is `"38;"` or `"48;"` for the dynamic `Rgb`/`C256`/`Hex` colors;
+    /// `shift` is added to a basic color's code (`0` for foreground, `10` for background).
+    /// `Reset` ignores both, since it's neither prefixed nor shifted.
+    pub(crate) fn write_ansi<W>(&self, w: &mut W, prefix: &str, shift: u8) -> Result<(), W::Error>
+    where
+        W: AnyWrite + ?Sized,
+    {
+        match self {
+            Color::Reset => w.write_any_str("0"),
+            Color::Rgb(r, g, b) => w.write_any_fmt(format_args!("{prefix}2;{r};{g};{b}")),
+            Color::C256(c) => w.write_any_fmt(format_args!("{prefix}5;{c}")),
+            Color::Hex(s) => {
+                let (r, g, b) = hex_to_rgb(s.as_str());
+                w.write_any_fmt(format_args!("{prefix}2;{r};{g};{b}"))
+            }
+            _ => w.write_any_fmt(format_args!("{}", self.basic_code() + shift)),
         }
     }
 }
@@ -69,12 +251,149 @@ impl fmt::Display for Color {
     }
 }
 
-fn hex_to_rgb(hex: String) -> (u8, u8, u8) {
-    let hex = u32::from_str_radix(&hex[1..], 16).unwrap_or(0);
-    let r = ((hex >> 16) & 0xFF) as u8;
-    let g = ((hex >> 8) & 0xFF) as u8;
-    let b = (hex & 0xFF) as u8;
-    (r, g, b)
+fn hex_to_rgb(hex: &str) -> (u8, u8, u8) {
+    match parse_hex_digits(hex) {
+        Ok((r, g, b, _)) => (r, g, b),
+        Err(_) => (0, 0, 0),
+    }
+}
+
+/// An invalid hex color string, e.g. wrong length or non-hex digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError {
+    input: String,
+    reason: &'static str,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid hex color '{}': {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Parse a hex color string (with or without a leading `#`) into its `(r, g, b, a)` channels,
+/// expanding `#RGB`/`#RGBA` short forms and defaulting to fully opaque when no alpha is given.
+fn parse_hex_digits(hex: &str) -> Result<(u8, u8, u8, u8), ParseColorError> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ParseColorError {
+            input: hex.to_string(),
+            reason: "contains non-hex characters",
+        });
+    }
+
+    let expand = |c: char| -> u8 {
+        let v = c.to_digit(16).unwrap() as u8;
+        v * 16 + v
+    };
+
+    let chars: Vec<char> = digits.chars().collect();
+    match chars.len() {
+        3 => Ok((expand(chars[0]), expand(chars[1]), expand(chars[2]), 255)),
+        4 => Ok((
+            expand(chars[0]),
+            expand(chars[1]),
+            expand(chars[2]),
+            expand(chars[3]),
+        )),
+        6 => {
+            let v = u32::from_str_radix(digits, 16).unwrap();
+            Ok((
+                ((v >> 16) & 0xFF) as u8,
+                ((v >> 8) & 0xFF) as u8,
+                (v & 0xFF) as u8,
+                255,
+            ))
+        }
+        8 => {
+            let v = u32::from_str_radix(digits, 16).unwrap();
+            Ok((
+                ((v >> 24) & 0xFF) as u8,
+                ((v >> 16) & 0xFF) as u8,
+                ((v >> 8) & 0xFF) as u8,
+                (v & 0xFF) as u8,
+            ))
+        }
+        _ => Err(ParseColorError {
+            input: hex.to_string(),
+            reason: "must be #RGB, #RRGGBB, #RGBA, or #RRGGBBAA",
+        }),
+    }
+}
+
+/// Resolve an xterm 256-color palette index to its approximate `(r, g, b)` triple: the 16
+/// basic colors, the 6x6x6 color cube (16-231), and the grayscale ramp (232-255).
+pub(crate) fn c256_to_rgb(index: u8) -> (u8, u8, u8) {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    if index < 16 {
+        ANSI16_RGB[index as usize]
+    } else if index < 232 {
+        let i = index - 16;
+        let r6 = i / 36;
+        let g6 = (i % 36) / 6;
+        let b6 = i % 6;
+        (
+            CUBE_LEVELS[r6 as usize],
+            CUBE_LEVELS[g6 as usize],
+            CUBE_LEVELS[b6 as usize],
+        )
+    } else {
+        let gray = 8 + 10 * (index - 232);
+        (gray, gray, gray)
+    }
+}
+
+fn squared_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    dr * dr + dg * dg + db * db
+}
+
+fn channel_to_cube_index(v: u8) -> u8 {
+    if v < 48 {
+        0
+    } else if v < 115 {
+        1
+    } else {
+        (v - 35) / 40
+    }
+}
+
+/// Find the xterm 256-color palette index closest to `(r, g, b)`, comparing the nearest
+/// 6x6x6 cube entry against the nearest grayscale ramp entry and keeping whichever is closer.
+fn nearest_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = 16
+        + 36 * channel_to_cube_index(r) as u16
+        + 6 * channel_to_cube_index(g) as u16
+        + channel_to_cube_index(b) as u16;
+    let cube_index = cube_index as u8;
+
+    let gray_level = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_step = (((gray_level as i32 - 8) as f32 / 10.0).round().clamp(0.0, 23.0)) as u8;
+    let gray_index = 232 + gray_step;
+
+    let cube_dist = squared_distance((r, g, b), c256_to_rgb(cube_index));
+    let gray_dist = squared_distance((r, g, b), c256_to_rgb(gray_index));
+
+    if gray_dist < cube_dist {
+        gray_index
+    } else {
+        cube_index
+    }
+}
+
+/// Find the basic ANSI color closest to `(r, g, b)` by squared RGB distance.
+fn nearest_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| squared_distance((r, g, b), *rgb))
+        .map(|(color, _)| color.clone())
+        .unwrap()
 }
 
 #[cfg(test)]
@@ -83,8 +402,40 @@ mod tests {
 
     #[test]
     fn test_hex_to_rgb() {
-        assert_eq!(hex_to_rgb("#FF0000".to_string()), (255, 0, 0));
-        assert_eq!(hex_to_rgb("#00FF00".to_string()), (0, 255, 0));
-        assert_eq!(hex_to_rgb("#0000FF".to_string()), (0, 0, 255));
+        assert_eq!(hex_to_rgb("#FF0000"), (255, 0, 0));
+        assert_eq!(hex_to_rgb("#00FF00"), (0, 255, 0));
+        assert_eq!(hex_to_rgb("#0000FF"), (0, 0, 255));
+    }
+
+    #[test]
+    fn test_hex_to_rgb_invalid_falls_back_to_black() {
+        assert_eq!(hex_to_rgb("#zzzzzz"), (0, 0, 0));
+    }
+
+    #[test]
+    fn from_hex_rejects_bad_input() {
+        assert!(Color::from_hex("#zzzzzz").is_err());
+        assert!(Color::from_hex("#12345").is_err());
+    }
+
+    #[test]
+    fn from_hex_expands_short_form() {
+        assert_eq!(Color::from_hex("#f00").unwrap().to_rgb(), (255, 0, 0));
+        assert_eq!(Color::from_hex("f00").unwrap().to_rgb(), (255, 0, 0));
+    }
+
+    #[test]
+    fn from_hex_parses_full_form_case_insensitively() {
+        assert_eq!(Color::from_hex("#FF8800").unwrap().to_rgb(), (255, 136, 0));
+        assert_eq!(Color::from_hex("ff8800").unwrap().to_rgb(), (255, 136, 0));
+    }
+
+    #[test]
+    fn from_hex_blends_alpha_against_background() {
+        let color = Color::from_hex_on("#ff0000ff", (0, 0, 0)).unwrap();
+        assert_eq!(color.to_rgb(), (255, 0, 0));
+
+        let transparent = Color::from_hex_on("#ff000000", (10, 20, 30)).unwrap();
+        assert_eq!(transparent.to_rgb(), (10, 20, 30));
     }
 }
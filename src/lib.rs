@@ -1,10 +1,14 @@
 pub mod colors;
 pub mod styles;
+pub mod write;
 
 use std::default;
+use std::fmt;
 
-use colors::Color;
+use colors::{Color, Level};
 use styles::Styles;
+use unicode_segmentation::UnicodeSegmentation;
+use write::AnyWrite;
 
 /// Style builde for terminal output. Call the methods to apply the desired styles and then call
 /// `render` to apply the styles to the text.
@@ -37,8 +41,16 @@ pub struct Lipbalm {
     reverse: bool,
     hidden: bool,
     strikethrough: bool,
+    double_underline: bool,
+    overline: bool,
+    framed: bool,
+    encircled: bool,
 
     link: Option<String>,
+
+    gradient: Option<Vec<Color>>,
+
+    color_level: Level,
 }
 
 impl Lipbalm {
@@ -54,7 +66,13 @@ impl Lipbalm {
             reverse: false,
             hidden: false,
             strikethrough: false,
+            double_underline: false,
+            overline: false,
+            framed: false,
+            encircled: false,
             link: None,
+            gradient: None,
+            color_level: Level::TrueColor,
         }
     }
 
@@ -98,6 +116,26 @@ impl Lipbalm {
         self
     }
 
+    pub fn double_underline(mut self, yes: bool) -> Lipbalm {
+        self.double_underline = yes;
+        self
+    }
+
+    pub fn overline(mut self, yes: bool) -> Lipbalm {
+        self.overline = yes;
+        self
+    }
+
+    pub fn framed(mut self, yes: bool) -> Lipbalm {
+        self.framed = yes;
+        self
+    }
+
+    pub fn encircled(mut self, yes: bool) -> Lipbalm {
+        self.encircled = yes;
+        self
+    }
+
     pub fn foreground(mut self, color: Color) -> Lipbalm {
         self.foreground = Some(color);
         self
@@ -108,8 +146,40 @@ impl Lipbalm {
         self
     }
 
+    /// Paint the text with a smooth two-color gradient instead of a flat foreground color.
+    /// Each grapheme gets its own interpolated color, from `start` at the beginning of the
+    /// text to `end` at the end.
+    pub fn foreground_gradient(mut self, start: Color, end: Color) -> Lipbalm {
+        self.gradient = Some(vec![start, end]);
+        self
+    }
+
+    /// Paint the text with a multi-stop gradient: `stops` are distributed evenly across the
+    /// text, with each grapheme's color interpolated between its two nearest stops. An empty
+    /// `stops` slice leaves any previously configured gradient untouched instead of producing
+    /// a gradient with nothing to interpolate between.
+    pub fn gradient_stops(mut self, stops: &[Color]) -> Lipbalm {
+        if !stops.is_empty() {
+            self.gradient = Some(stops.to_vec());
+        }
+        self
+    }
+
+    /// Downgrade `Rgb`/`Hex`/`C256` colors to the nearest color the given terminal capability
+    /// `level` can render. Defaults to `Level::TrueColor`, i.e. no downgrade.
+    pub fn color_level(mut self, level: Level) -> Lipbalm {
+        self.color_level = level;
+        self
+    }
+
     fn apply_foreground(&self) -> String {
-        let value = self.foreground.unwrap();
+        self.foreground_code(self.foreground.as_ref().unwrap())
+    }
+
+    /// Resolve `color` to its foreground SGR code at `self.color_level`, downgrading it first.
+    /// Shared by `apply_foreground` and gradient rendering so both respect the same capability.
+    fn foreground_code(&self, color: &Color) -> String {
+        let value = color.downgrade(self.color_level);
         let ansi = value.to_ansi();
         match value {
             Color::Rgb(_, _, _) | Color::C256(_) | Color::Hex(_) => {
@@ -125,7 +195,11 @@ impl Lipbalm {
     }
 
     fn apply_background(&self) -> String {
-        let value = self.background.unwrap_or(Color::Reset);
+        let value = self
+            .background
+            .as_ref()
+            .unwrap_or(&Color::Reset)
+            .downgrade(self.color_level);
         let ansi = value.to_ansi();
         match value {
             Color::Reset => ansi,
@@ -139,8 +213,8 @@ impl Lipbalm {
         }
     }
 
-    /// Apply the styles to the text and return the result as a string.
-    pub fn render(&self, text: &str) -> String {
+    /// The full list of SGR codes this style applies, in the same order `render` emits them.
+    fn style_codes(&self) -> Vec<String> {
         let mut styles: Vec<String> = Vec::new();
 
         if self.foreground.is_some() {
@@ -183,21 +257,301 @@ impl Lipbalm {
             styles.push(Styles::Strikethrough.to_ansi());
         }
 
-        let styles = styles.iter().map(|s| s.as_str()).filter(|s| !s.is_empty());
-        let result = format!(
-            "\x1b[{}m{}\x1b[0m",
-            styles.collect::<Vec<&str>>().join(";"),
-            text
-        );
+        if self.double_underline {
+            styles.push(Styles::DoubleUnderline.to_ansi());
+        }
+
+        if self.overline {
+            styles.push(Styles::Overline.to_ansi());
+        }
+
+        if self.framed {
+            styles.push(Styles::Framed.to_ansi());
+        }
+
+        if self.encircled {
+            styles.push(Styles::Encircled.to_ansi());
+        }
+
+        styles.retain(|s| !s.is_empty());
+        styles
+    }
+
+    /// Apply the styles to the text and return the result as a string.
+    pub fn render(&self, text: &str) -> String {
+        if self.gradient.is_some() {
+            return self.render_gradient(text);
+        }
+
+        let mut buf = String::new();
+        self.write_to(&mut buf as &mut dyn fmt::Write, text)
+            .expect("writing to a String cannot fail");
+
+        if let Some(link) = &self.link {
+            format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", link, buf)
+        } else {
+            buf
+        }
+    }
+
+    /// Stream the prefix SGR sequence, the text, and the trailing reset directly to `w`, with
+    /// no per-call heap traffic for plain attributes and basic colors — `Rgb`/`C256`/`Hex`
+    /// colors still format their dynamic digits through `write_any_fmt`, but never via an
+    /// intermediate `String`. Generic over both `std::io::Write` and `std::fmt::Write` sinks:
+    /// ```
+    /// use std::io::Write;
+    /// use lipbalm::Lipbalm;
+    /// use lipbalm::colors::Color;
+    ///
+    /// let lipbalm = Lipbalm::new().foreground(Color::Red);
+    /// let mut stdout = std::io::stdout();
+    /// lipbalm.write_to(&mut stdout as &mut dyn Write, "Hello, world!").unwrap();
+    /// ```
+    /// Does not apply the gradient mode or hyperlink wrapping; use `render` for those.
+    pub fn write_to<W>(&self, w: &mut W, text: &str) -> Result<(), W::Error>
+    where
+        W: AnyWrite + ?Sized,
+    {
+        w.write_any_str("\x1b[")?;
+
+        let mut wrote = false;
+
+        if let Some(foreground) = &self.foreground {
+            write_sep(w, &mut wrote)?;
+            foreground.downgrade(self.color_level).write_ansi(w, "38;", 0)?;
+        }
+        if let Some(background) = &self.background {
+            write_sep(w, &mut wrote)?;
+            background.downgrade(self.color_level).write_ansi(w, "48;", 10)?;
+        }
+        if self.bold {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Bold.code())?;
+        }
+        if self.dim {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Dim.code())?;
+        }
+        if self.italic {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Italic.code())?;
+        }
+        if self.underline {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Underline.code())?;
+        }
+        if self.blink {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Blink.code())?;
+        }
+        if self.reverse {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Reverse.code())?;
+        }
+        if self.hidden {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Hidden.code())?;
+        }
+        if self.strikethrough {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Strikethrough.code())?;
+        }
+        if self.double_underline {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::DoubleUnderline.code())?;
+        }
+        if self.overline {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Overline.code())?;
+        }
+        if self.framed {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Framed.code())?;
+        }
+        if self.encircled {
+            write_sep(w, &mut wrote)?;
+            w.write_any_str(Styles::Encircled.code())?;
+        }
+
+        w.write_any_str("m")?;
+        w.write_any_str(text)?;
+        w.write_any_str("\x1b[0m")
+    }
+
+    /// Render a sequence of adjacent styled spans, emitting only the SGR codes needed to
+    /// transition from one span's style to the next instead of a full reset-and-reapply
+    /// around every piece. If the next span's codes are a strict superset of the current
+    /// span's (same colors, only extra attributes turned on), only the newly-added codes are
+    /// emitted; otherwise a reset (`0`) precedes the next span's full code list. A single
+    /// trailing reset closes the whole sequence.
+    ///
+    /// Only plain SGR attributes transition this way. Panics if any span sets `link` or
+    /// `foreground_gradient`, since hyperlink wrapping and per-grapheme gradients don't fit
+    /// the SGR-transition model; render those spans individually with `render` instead.
+    pub fn render_sequence(spans: &[(Lipbalm, &str)]) -> String {
+        let mut result = String::new();
+        let mut prev_codes: Option<Vec<String>> = None;
+
+        for (style, text) in spans {
+            assert!(
+                style.link.is_none() && style.gradient.is_none(),
+                "render_sequence does not support spans with `link` or `foreground_gradient`; \
+                 render them individually with `render` instead"
+            );
+
+            let codes = style.style_codes();
+
+            let transition: Vec<String> = match &prev_codes {
+                Some(prev) if prev.iter().all(|c| codes.contains(c)) => {
+                    codes.iter().filter(|c| !prev.contains(c)).cloned().collect()
+                }
+                Some(_) => {
+                    let mut full = vec!["0".to_string()];
+                    full.extend(codes.clone());
+                    full
+                }
+                None => codes.clone(),
+            };
+
+            if !transition.is_empty() {
+                result.push_str(&format!("\x1b[{}m", transition.join(";")));
+            }
+            result.push_str(text);
+
+            prev_codes = Some(codes);
+        }
+
+        result.push_str("\x1b[0m");
+        result
+    }
+
+    /// Render `text` with each grapheme's foreground color interpolated along `self.gradient`,
+    /// keeping the background and any other active styles constant across the whole string.
+    fn render_gradient(&self, text: &str) -> String {
+        let stops = self.gradient.as_ref().unwrap();
+
+        let mut extra_styles: Vec<String> = Vec::new();
+
+        if self.background.is_some() {
+            extra_styles.push(self.apply_background());
+        }
+
+        if self.bold {
+            extra_styles.push(Styles::Bold.to_ansi());
+        }
+
+        if self.dim {
+            extra_styles.push(Styles::Dim.to_ansi());
+        }
+
+        if self.italic {
+            extra_styles.push(Styles::Italic.to_ansi());
+        }
+
+        if self.underline {
+            extra_styles.push(Styles::Underline.to_ansi());
+        }
+
+        if self.blink {
+            extra_styles.push(Styles::Blink.to_ansi());
+        }
+
+        if self.reverse {
+            extra_styles.push(Styles::Reverse.to_ansi());
+        }
+
+        if self.hidden {
+            extra_styles.push(Styles::Hidden.to_ansi());
+        }
+
+        if self.strikethrough {
+            extra_styles.push(Styles::Strikethrough.to_ansi());
+        }
+
+        if self.double_underline {
+            extra_styles.push(Styles::DoubleUnderline.to_ansi());
+        }
+
+        if self.overline {
+            extra_styles.push(Styles::Overline.to_ansi());
+        }
+
+        if self.framed {
+            extra_styles.push(Styles::Framed.to_ansi());
+        }
+
+        if self.encircled {
+            extra_styles.push(Styles::Encircled.to_ansi());
+        }
+
+        let suffix = if extra_styles.is_empty() {
+            String::new()
+        } else {
+            format!(";{}", extra_styles.join(";"))
+        };
+
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        let n = graphemes.len();
+
+        let mut buf = String::new();
+        for (i, grapheme) in graphemes.iter().enumerate() {
+            let t = if n <= 1 {
+                0.0
+            } else {
+                i as f32 / (n - 1) as f32
+            };
+            let (r, g, b) = gradient_color_at(stops, t);
+            let fg = self.foreground_code(&Color::Rgb(r, g, b));
+            buf.push_str(&format!("\x1b[{}{}m{}\x1b[0m", fg, suffix, grapheme));
+        }
 
         if let Some(link) = &self.link {
-            format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", link, result)
+            format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", link, buf)
         } else {
-            result
+            buf
         }
     }
 }
 
+/// Writes a `;` separator to `w` if a code has already been written for this sequence, then
+/// marks `wrote` so the next code knows to separate. Callers write their own code right after.
+fn write_sep<W>(w: &mut W, wrote: &mut bool) -> Result<(), W::Error>
+where
+    W: AnyWrite + ?Sized,
+{
+    if *wrote {
+        w.write_any_str(";")?;
+    }
+    *wrote = true;
+    Ok(())
+}
+
+/// Interpolate the color at position `t` (`0.0..=1.0`) along a sequence of gradient stops.
+/// With a single stop, that stop's color is used outright.
+fn gradient_color_at(stops: &[Color], t: f32) -> (u8, u8, u8) {
+    if stops.len() == 1 {
+        return stops[0].to_rgb();
+    }
+
+    let segments = stops.len() - 1;
+    let s = t * segments as f32;
+    let segment = (s.floor() as usize).min(segments - 1);
+    let local_t = s - segment as f32;
+
+    let (r1, g1, b1) = stops[segment].to_rgb();
+    let (r2, g2, b2) = stops[segment + 1].to_rgb();
+
+    (
+        lerp_channel(r1, r2, local_t),
+        lerp_channel(g1, g2, local_t),
+        lerp_channel(b1, b2, local_t),
+    )
+}
+
+fn lerp_channel(start: u8, end: u8, t: f32) -> u8 {
+    (start as f32 + (end as f32 - start as f32) * t).round() as u8
+}
+
 impl default::Default for Lipbalm {
     fn default() -> Lipbalm {
         Lipbalm::new()
@@ -226,6 +580,17 @@ mod tests {
         assert_eq!(result, "\x1b[31;42;1;4mHello, world!\x1b[0m");
     }
 
+    #[test]
+    fn with_decorations() {
+        let result = Lipbalm::new()
+            .double_underline(true)
+            .overline(true)
+            .framed(true)
+            .encircled(true)
+            .render("Hello, world!");
+        assert_eq!(result, "\x1b[21;53;51;52mHello, world!\x1b[0m");
+    }
+
     #[test]
     fn with_link() {
         let result = Lipbalm::new()
@@ -241,8 +606,8 @@ mod tests {
     #[test]
     fn with_hex_color() {
         let result = Lipbalm::new()
-            .foreground(Color::Hex("#ff0000"))
-            .background(Color::Hex("#00ff00"))
+            .foreground(Color::from_hex("#ff0000").unwrap())
+            .background(Color::from_hex("#00ff00").unwrap())
             .render("Hello, world!");
         assert_eq!(
             result,
@@ -290,4 +655,114 @@ mod tests {
             .render("Hello, world!");
         assert_eq!(result, "\x1b[mHello, world!\x1b[0m");
     }
+
+    #[test]
+    fn with_foreground_gradient_single_char() {
+        let result = Lipbalm::new()
+            .foreground_gradient(Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255))
+            .render("A");
+        assert_eq!(result, "\x1b[38;2;0;0;0mA\x1b[0m");
+    }
+
+    #[test]
+    fn with_foreground_gradient() {
+        let result = Lipbalm::new()
+            .foreground_gradient(Color::Rgb(0, 0, 0), Color::Rgb(100, 0, 0))
+            .render("abc");
+        assert_eq!(
+            result,
+            "\x1b[38;2;0;0;0ma\x1b[0m\x1b[38;2;50;0;0mb\x1b[0m\x1b[38;2;100;0;0mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn with_gradient_stops() {
+        let result = Lipbalm::new()
+            .gradient_stops(&[Color::Rgb(0, 0, 0), Color::Rgb(100, 0, 0), Color::Rgb(0, 0, 100)])
+            .render("abc");
+        assert_eq!(
+            result,
+            "\x1b[38;2;0;0;0ma\x1b[0m\x1b[38;2;100;0;0mb\x1b[0m\x1b[38;2;0;0;100mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn with_empty_gradient_stops_does_not_panic() {
+        let result = Lipbalm::new().gradient_stops(&[]).render("abc");
+        assert_eq!(result, "\x1b[mabc\x1b[0m");
+    }
+
+    #[test]
+    fn render_sequence_extends_with_superset_style() {
+        let spans = [
+            (Lipbalm::new().foreground(Color::Red), "abc"),
+            (Lipbalm::new().foreground(Color::Red).bold(true), "def"),
+        ];
+        let result = Lipbalm::render_sequence(&spans);
+        assert_eq!(result, "\x1b[31mabc\x1b[1mdef\x1b[0m");
+    }
+
+    #[test]
+    fn render_sequence_resets_on_incompatible_style() {
+        let spans = [
+            (Lipbalm::new().foreground(Color::Red).bold(true), "abc"),
+            (Lipbalm::new().foreground(Color::Green), "def"),
+        ];
+        let result = Lipbalm::render_sequence(&spans);
+        assert_eq!(result, "\x1b[31;1mabc\x1b[0;32mdef\x1b[0m");
+    }
+
+    #[test]
+    fn render_sequence_single_span() {
+        let spans = [(Lipbalm::new().foreground(Color::Red), "abc")];
+        let result = Lipbalm::render_sequence(&spans);
+        assert_eq!(result, "\x1b[31mabc\x1b[0m");
+    }
+
+    #[test]
+    fn with_truecolor_by_default() {
+        let result = Lipbalm::new()
+            .foreground(Color::Rgb(1, 2, 3))
+            .render("x");
+        assert_eq!(result, "\x1b[38;2;1;2;3mx\x1b[0m");
+    }
+
+    #[test]
+    fn downgrades_rgb_to_ansi256() {
+        let result = Lipbalm::new()
+            .foreground(Color::Rgb(255, 0, 0))
+            .color_level(Level::Ansi256)
+            .render("x");
+        assert_eq!(result, "\x1b[38;5;196mx\x1b[0m");
+    }
+
+    #[test]
+    fn downgrades_rgb_to_ansi16() {
+        let result = Lipbalm::new()
+            .foreground(Color::Rgb(255, 0, 0))
+            .color_level(Level::Ansi16)
+            .render("x");
+        assert_eq!(result, "\x1b[91mx\x1b[0m");
+    }
+
+    #[test]
+    fn write_to_fmt_writer() {
+        let mut buf = String::new();
+        Lipbalm::new()
+            .foreground(Color::Red)
+            .bold(true)
+            .write_to(&mut buf as &mut dyn std::fmt::Write, "Hello, world!")
+            .unwrap();
+        assert_eq!(buf, "\x1b[31;1mHello, world!\x1b[0m");
+    }
+
+    #[test]
+    fn write_to_io_writer() {
+        let mut buf: Vec<u8> = Vec::new();
+        Lipbalm::new()
+            .foreground(Color::Red)
+            .write_to(&mut buf as &mut dyn std::io::Write, "Hello, world!")
+            .unwrap();
+        assert_eq!(buf, b"\x1b[31mHello, world!\x1b[0m");
+    }
 }
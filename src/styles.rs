@@ -9,20 +9,34 @@ pub enum Styles {
     Reverse,
     Hidden,
     Strikethrough,
+    DoubleUnderline,
+    Framed,
+    Encircled,
+    Overline,
 }
 
 impl Styles {
     pub fn to_ansi(&self) -> String {
+        self.code().to_string()
+    }
+
+    /// The bare numeric SGR code as a string literal, with no allocation — used by hot paths
+    /// that stream codes straight into a writer instead of collecting into a `Vec<String>`.
+    pub(crate) fn code(&self) -> &'static str {
         match self {
-            Styles::Reset => "0".to_string(),
-            Styles::Bold => "1".to_string(),
-            Styles::Dim => "2".to_string(),
-            Styles::Italic => "3".to_string(),
-            Styles::Underline => "4".to_string(),
-            Styles::Blink => "5".to_string(),
-            Styles::Reverse => "7".to_string(),
-            Styles::Hidden => "8".to_string(),
-            Styles::Strikethrough => "9".to_string(),
+            Styles::Reset => "0",
+            Styles::Bold => "1",
+            Styles::Dim => "2",
+            Styles::Italic => "3",
+            Styles::Underline => "4",
+            Styles::Blink => "5",
+            Styles::Reverse => "7",
+            Styles::Hidden => "8",
+            Styles::Strikethrough => "9",
+            Styles::DoubleUnderline => "21",
+            Styles::Framed => "51",
+            Styles::Encircled => "52",
+            Styles::Overline => "53",
         }
     }
 }
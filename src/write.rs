@@ -0,0 +1,49 @@
+use std::fmt;
+use std::io;
+
+mod sealed {
+    use std::fmt;
+    use std::io;
+
+    /// Prevents downstream crates from implementing `AnyWrite` for their own types; only the
+    /// two writer kinds below are ever meant to plug into `Lipbalm::write_to`.
+    pub trait Sealed {}
+
+    impl<'a> Sealed for dyn fmt::Write + 'a {}
+    impl<'a> Sealed for dyn io::Write + 'a {}
+}
+
+/// Unifies writing a `&str` to `std::io::Write` and `std::fmt::Write` sinks so `Lipbalm::write_to`
+/// can stream styled text into either without allocating an intermediate `String`. Implemented
+/// for the trait objects rather than as a blanket generic impl, since a type could otherwise
+/// implement both `io::Write` and `fmt::Write` at once. Sealed: only the two impls below exist.
+pub trait AnyWrite: sealed::Sealed {
+    type Error;
+
+    fn write_any_str(&mut self, s: &str) -> Result<(), Self::Error>;
+    fn write_any_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), Self::Error>;
+}
+
+impl<'a> AnyWrite for dyn fmt::Write + 'a {
+    type Error = fmt::Error;
+
+    fn write_any_str(&mut self, s: &str) -> fmt::Result {
+        fmt::Write::write_str(self, s)
+    }
+
+    fn write_any_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        fmt::Write::write_fmt(self, args)
+    }
+}
+
+impl<'a> AnyWrite for dyn io::Write + 'a {
+    type Error = io::Error;
+
+    fn write_any_str(&mut self, s: &str) -> io::Result<()> {
+        io::Write::write_all(self, s.as_bytes())
+    }
+
+    fn write_any_fmt(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
+        io::Write::write_fmt(self, args)
+    }
+}